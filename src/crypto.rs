@@ -0,0 +1,90 @@
+// crypto spot prices, fetched incrementally from CoinGecko's
+// coins/{id}/market_chart/range endpoint; priced in whatever vs_currency the
+// caller asks for, not rebased against EUR
+
+use std::collections::BTreeMap;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+
+const API_BASE: &str = "https://api.coingecko.com/api/v3";
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// coin ids and vs_currency codes are lowercase alphanumerics and hyphens;
+// everything else (notably `&` and `=`) gets percent-encoded so a caller
+// can't inject extra query parameters into the upstream request
+const CRYPTO_PARAM: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-');
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    pub timestamp: i64,
+    pub price: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketChartRange {
+    prices: Vec<(i64, f64)>,
+}
+
+fn market_chart_range_url(coin: &str, vs_currency: &str, from_ts: i64, to_ts: i64) -> String {
+    let coin = utf8_percent_encode(coin, CRYPTO_PARAM);
+    let vs_currency = utf8_percent_encode(vs_currency, CRYPTO_PARAM);
+    format!(
+        "{}/coins/{}/market_chart/range?vs_currency={}&from={}&to={}",
+        API_BASE, coin, vs_currency, from_ts, to_ts
+    )
+}
+
+// CoinGecko returns a [millis, price] pair roughly every few hours; each is
+// floored to its day boundary, keeping only the last quote seen for that day
+pub async fn fetch_crypto(
+    coin: &str,
+    vs_currency: &str,
+    from_ts: i64,
+    to_ts: i64,
+) -> Result<Vec<Quote>, Error> {
+    let url = market_chart_range_url(coin, vs_currency, from_ts, to_ts);
+
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| Error::FetcherError(e.to_string()))?
+        .json::<MarketChartRange>()
+        .await
+        .map_err(|e| Error::FetcherError(e.to_string()))?;
+
+    // later entries win on a collision, so iterating in the API's
+    // chronological order keeps the last quote seen per day.
+    let mut by_day: BTreeMap<i64, f64> = BTreeMap::new();
+    for (millis, price) in response.prices {
+        let day = (millis / 1000 / SECONDS_PER_DAY) * SECONDS_PER_DAY;
+        by_day.insert(day, price);
+    }
+
+    Ok(by_day
+        .into_iter()
+        .map(|(timestamp, price)| Quote { timestamp, price })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_market_chart_range_url_leaves_plain_ids_unescaped() {
+        let url = market_chart_range_url("bitcoin", "usd", 0, 1);
+        assert_eq!(
+            url,
+            "https://api.coingecko.com/api/v3/coins/bitcoin/market_chart/range?vs_currency=usd&from=0&to=1"
+        );
+    }
+
+    #[test]
+    fn test_market_chart_range_url_percent_encodes_injection_attempts() {
+        let url = market_chart_range_url("bitcoin&from=0", "usd", 0, 1);
+        assert!(!url.contains("&from=0&vs_currency"));
+        assert!(url.contains("bitcoin%26from%3D0"));
+    }
+}
@@ -3,14 +3,16 @@ use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use chrono::naive::NaiveDate;
-use chrono::Duration;
+use chrono::naive::{NaiveDate, NaiveDateTime};
+use chrono::{Datelike, Duration, Utc, Weekday};
 use anyhow::{anyhow, Error, Context};
 use serde::{de::DeserializeOwned, Serialize};
 use sled::IVec;
 use tokio_executor::blocking;
 
+use crate::crypto::{self, Quote};
 use crate::currencies::{self, Currency, Date};
+use crate::errors::Error as ApiError;
 
 pub fn date_as_key(date: &str) -> Result<Vec<u8>, Error> {
     let date = NaiveDate::from_str(date)?
@@ -21,6 +23,109 @@ pub fn date_as_key(date: &str) -> Result<Vec<u8>, Error> {
     Ok(date)
 }
 
+// crypto/{coin}/{vs}/ + big-endian day timestamp, kept separate from the fiat keyspace
+fn crypto_key(coin: &str, vs_currency: &str, timestamp: i64) -> Vec<u8> {
+    let mut key = crypto_prefix(coin, vs_currency);
+    key.extend_from_slice(&timestamp.to_be_bytes());
+    key
+}
+
+fn crypto_prefix(coin: &str, vs_currency: &str) -> Vec<u8> {
+    format!("crypto/{}/{}/", coin, vs_currency).into_bytes()
+}
+
+fn crypto_current_key(coin: &str, vs_currency: &str) -> Vec<u8> {
+    format!("crypto/current/{}/{}", coin, vs_currency).into_bytes()
+}
+
+// marks `day` as a confirmed non-publishing day so find_gaps stops re-flagging it
+fn known_gap_key(day: NaiveDate) -> Vec<u8> {
+    let mut key = b"known_gap/".to_vec();
+    key.extend_from_slice(&day.and_hms(0, 0, 0).timestamp().to_be_bytes());
+    key
+}
+
+// Easter Sunday for `year`, via the anonymous Gregorian algorithm
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd(year, month as u32, day as u32)
+}
+
+// ECB's published closing days: New Year's, Good Friday, Easter Monday, Labour Day,
+// Christmas Day and 26 December
+fn is_ecb_holiday(day: NaiveDate) -> bool {
+    let easter = easter_sunday(day.year());
+    day == NaiveDate::from_ymd(day.year(), 1, 1)
+        || day == easter - Duration::days(2) // Good Friday
+        || day == easter + Duration::days(1) // Easter Monday
+        || day == NaiveDate::from_ymd(day.year(), 5, 1)
+        || day == NaiveDate::from_ymd(day.year(), 12, 25)
+        || day == NaiveDate::from_ymd(day.year(), 12, 26)
+}
+
+fn business_days(start: NaiveDate, end: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    let mut day = start;
+    std::iter::from_fn(move || {
+        if day > end {
+            return None;
+        }
+        let current = day;
+        day = day + Duration::days(1);
+        Some(current)
+    })
+    .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+    .filter(|day| !is_ecb_holiday(*day))
+}
+
+// new_rate_x = rate_x / rate_base, the way CoinGecko's vs_currency rebases a market
+fn rebase(mut date: Date, base: &str) -> Result<Date, Error> {
+    let base_rate = date
+        .currencies
+        .iter()
+        .find(|currency| currency.name == base)
+        .map(|currency| currency.rate)
+        .ok_or_else(|| ApiError::InvalidBase(base.to_string()))?;
+
+    for currency in date.currencies.iter_mut() {
+        currency.rate /= base_rate;
+    }
+
+    Ok(date)
+}
+
+fn rebase_opt(date: Date, base: Option<&str>) -> Result<Date, Error> {
+    match base {
+        Some(base) => rebase(date, base).map_err(Error::from),
+        None => Ok(date),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RangeStats {
+    pub start_date: String,
+    pub end_date: String,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub first: f64,
+    pub last: f64,
+    pub change_pct: f64,
+    pub volatility: f64,
+}
+
 #[derive(Clone)]
 pub struct Db {
     inner: Arc<sled::Db>,
@@ -30,7 +135,9 @@ impl Db {
     pub async fn init<P: AsRef<Path>>(path: P) -> Result<Db, Error> {
         if path.as_ref().exists() {
             log::info!("previous db file found, going to open it");
-            Db::open(path)
+            let db = Db::open(path)?;
+            db.heal_gaps().await?;
+            Ok(db)
         } else {
             Db::bootstrap_new(path).await
         }
@@ -73,7 +180,7 @@ impl Db {
         Ok(db)
     }
 
-    pub async fn get_current_rates(&self) -> Result<Date, Error> {
+    pub async fn get_current_rates(&self, base: Option<&str>) -> Result<Date, Error> {
         let current = self
             .get::<Vec<u8>>(b"current")
             .await?
@@ -83,12 +190,43 @@ impl Db {
             anyhow!("could not find `current` reference rates on the database")
         })?;
 
-        Ok(date)
+        rebase_opt(date, base)
     }
 
-    pub async fn get_day_rates(&self, day: &str) -> Result<Option<Date>, Error> {
+    pub async fn get_day_rates(&self, day: &str, base: Option<&str>) -> Result<Option<Date>, Error> {
         match self.get::<Date>(&date_as_key(day)?).await? {
-            Some(date) => Ok(Some(date)),
+            Some(date) => Ok(Some(rebase_opt(date, base)?)),
+            None => Ok(None),
+        }
+    }
+
+    // like get_day_rates, but falls back to the latest preceding entry when
+    // `day` is a weekend/holiday the ECB never published
+    pub async fn get_day_rates_or_latest(
+        &self,
+        day: &str,
+        base: Option<&str>,
+    ) -> Result<Option<Date>, Error> {
+        if let Some(date) = self.get::<Date>(&date_as_key(day)?).await? {
+            return Ok(Some(rebase_opt(date, base)?));
+        }
+
+        let key = date_as_key(day)?;
+        let date = self
+            .execute(move |db| -> Result<Option<Date>, Error> {
+                db.range(..=key)
+                    .next_back()
+                    .map(|result| {
+                        let (_key, value) = result
+                            .with_context(|| format!("could not get latest preceding rates from db"))?;
+                        bincode::deserialize::<Date>(&value).map_err(Error::from)
+                    })
+                    .transpose()
+            })
+            .await?;
+
+        match date {
+            Some(date) => Ok(Some(rebase_opt(date, base)?)),
             None => Ok(None),
         }
     }
@@ -97,6 +235,7 @@ impl Db {
         &self,
         start_at: NaiveDate,
         end_at: NaiveDate,
+        base: Option<&str>,
     ) -> Result<Vec<Date>, Error> {
         let range_start = date_as_key(&start_at.to_string())?;
         let range_end = date_as_key(&end_at.to_string())?;
@@ -112,7 +251,154 @@ impl Db {
                     .with_context(|| format!("could not get range from db"))
             })
             .await?;
-        Ok(dates)
+
+        dates.into_iter().map(|date| rebase_opt(date, base)).collect()
+    }
+
+    // summary stats for `symbol` across start_at..=end_at, streamed from a
+    // single sled range pass instead of materializing a Vec<Date>
+    pub async fn get_range_stats(
+        &self,
+        start_at: NaiveDate,
+        end_at: NaiveDate,
+        symbol: &str,
+    ) -> Result<RangeStats, Error> {
+        if start_at > end_at {
+            return Err(ApiError::InvalidDateRange.into());
+        }
+
+        let range_start = date_as_key(&start_at.to_string())?;
+        let range_end = date_as_key(&end_at.to_string())?;
+        let symbol = symbol.to_string();
+
+        let stats = self
+            .execute(move |db| -> Result<RangeStats, Error> {
+                let mut count = 0u64;
+                let mut sum = 0.0;
+                let mut sum_sq = 0.0;
+                let mut min = f64::INFINITY;
+                let mut max = f64::NEG_INFINITY;
+                let mut first = None;
+                let mut last = None;
+                let mut start_date = None;
+                let mut end_date = None;
+
+                for result in db.range(range_start..=range_end) {
+                    let (_key, value) = result?;
+                    let date = bincode::deserialize::<Date>(&value)?;
+                    let rate = match date.currencies.iter().find(|c| c.name == symbol) {
+                        Some(currency) => currency.rate,
+                        None => continue,
+                    };
+
+                    if first.is_none() {
+                        first = Some(rate);
+                        start_date = Some(date.value.clone());
+                    }
+                    last = Some(rate);
+                    end_date = Some(date.value.clone());
+
+                    count += 1;
+                    sum += rate;
+                    sum_sq += rate * rate;
+                    min = min.min(rate);
+                    max = max.max(rate);
+                }
+
+                let (first, last, start_date, end_date) =
+                    match (first, last, start_date, end_date) {
+                        (Some(f), Some(l), Some(s), Some(e)) => (f, l, s, e),
+                        _ => return Err(ApiError::InvalidSymbol.into()),
+                    };
+
+                let mean = sum / count as f64;
+                let variance = (sum_sq / count as f64 - mean * mean).max(0.0);
+
+                Ok(RangeStats {
+                    start_date,
+                    end_date,
+                    min,
+                    max,
+                    mean,
+                    first,
+                    last,
+                    change_pct: (last - first) / first * 100.0,
+                    volatility: variance.sqrt(),
+                })
+            })
+            .await?;
+
+        Ok(stats)
+    }
+
+    pub async fn get_crypto_current(&self, coin: &str, vs_currency: &str) -> Result<Quote, Error> {
+        let timestamp = self
+            .get::<i64>(&crypto_current_key(coin, vs_currency))
+            .await?
+            .ok_or_else(|| anyhow!("no crypto quotes stored yet for {}/{}", coin, vs_currency))?;
+
+        self.get::<Quote>(&crypto_key(coin, vs_currency, timestamp))
+            .await?
+            .ok_or_else(|| anyhow!("missing crypto quote for {}/{} at {}", coin, vs_currency, timestamp))
+    }
+
+    pub async fn get_crypto_day(
+        &self,
+        coin: &str,
+        vs_currency: &str,
+        day: NaiveDate,
+    ) -> Result<Option<Quote>, Error> {
+        let timestamp = day.and_hms(0, 0, 0).timestamp();
+        self.get::<Quote>(&crypto_key(coin, vs_currency, timestamp))
+            .await
+    }
+
+    pub async fn get_crypto_range(
+        &self,
+        coin: &str,
+        vs_currency: &str,
+        start_at: NaiveDate,
+        end_at: NaiveDate,
+    ) -> Result<Vec<Quote>, Error> {
+        let range_start = crypto_key(coin, vs_currency, start_at.and_hms(0, 0, 0).timestamp());
+        let range_end = crypto_key(coin, vs_currency, end_at.and_hms(0, 0, 0).timestamp());
+
+        self.execute(move |db| {
+            db.range(range_start..=range_end)
+                .map(|result| {
+                    let (_key, value) = result?;
+                    bincode::deserialize::<Quote>(&value).map_err(Error::from)
+                })
+                .collect::<Result<Vec<Quote>, Error>>()
+        })
+        .await
+    }
+
+    // only fetches the gap between the latest stored day and today
+    pub async fn update_crypto(&self, coin: &str, vs_currency: &str) -> Result<(), Error> {
+        let latest = self.get::<i64>(&crypto_current_key(coin, vs_currency)).await?;
+        let from_ts = match latest {
+            Some(ts) => ts + 86_400,
+            // CoinGecko's earliest coverage for most coins.
+            None => NaiveDate::from_str("2013-04-28")?.and_hms(0, 0, 0).timestamp(),
+        };
+        let to_ts = Utc::now().naive_utc().date().and_hms(0, 0, 0).timestamp();
+
+        if from_ts > to_ts {
+            log::debug!("{}/{} crypto quotes already up to date", coin, vs_currency);
+            return Ok(());
+        }
+
+        let quotes = crypto::fetch_crypto(coin, vs_currency, from_ts, to_ts).await?;
+        for quote in &quotes {
+            self.put(&crypto_key(coin, vs_currency, quote.timestamp), quote)
+                .await?;
+        }
+        if let Some(last) = quotes.last() {
+            self.put(&crypto_current_key(coin, vs_currency), &last.timestamp)
+                .await?;
+        }
+        Ok(())
     }
 
     async fn put<T>(&self, key: &[u8], value: &T) -> Result<Option<IVec>, Error>
@@ -145,12 +431,12 @@ impl Db {
 
     pub async fn update(&self) -> Result<(), Error> {
         let current = currencies::fetch_daily().await?.value_as_date()?;
-        let db_current = self.get_current_rates().await?.value_as_date()?;
+        let db_current = self.get_current_rates(None).await?.value_as_date()?;
 
         match current.cmp(&db_current) {
             Ordering::Equal => {
                 log::debug!("database currencies up to date");
-                return Ok(());
+                return self.heal_gaps().await;
             }
 
             Ordering::Greater => {
@@ -183,6 +469,111 @@ impl Db {
                 ))
             }
         }
+
+        self.heal_gaps().await
+    }
+
+    // scans the stored keyspace directly for every expected ECB business day
+    // missing between the earliest stored entry and `current`
+    async fn find_gaps(&self, current: NaiveDate) -> Result<Vec<NaiveDate>, Error> {
+        let (stored, known_gaps) = self
+            .execute(|db| {
+                let mut stored = std::collections::BTreeSet::new();
+                let mut known_gaps = std::collections::BTreeSet::new();
+
+                for result in db.iter() {
+                    let (key, _value) = match result {
+                        Ok(entry) => entry,
+                        Err(_) => continue,
+                    };
+
+                    if key.len() == 8 {
+                        let mut bytes = [0u8; 8];
+                        bytes.copy_from_slice(&key);
+                        stored.insert(NaiveDateTime::from_timestamp(i64::from_be_bytes(bytes), 0).date());
+                    } else if key.starts_with(b"known_gap/") && key.len() >= 8 {
+                        let mut bytes = [0u8; 8];
+                        bytes.copy_from_slice(&key[key.len() - 8..]);
+                        known_gaps.insert(NaiveDateTime::from_timestamp(i64::from_be_bytes(bytes), 0).date());
+                    }
+                }
+
+                (stored, known_gaps)
+            })
+            .await;
+
+        let earliest = match stored.iter().next() {
+            Some(day) => *day,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(business_days(earliest, current)
+            .filter(|day| !stored.contains(day) && !known_gaps.contains(day))
+            .collect())
+    }
+
+    // backfills missing days found by find_gaps; safe to re-run since inserts
+    // are idempotent
+    pub async fn heal_gaps(&self) -> Result<(), Error> {
+        let current = self.get_current_rates(None).await?.value_as_date()?;
+        let gaps = self.find_gaps(current).await?;
+
+        if gaps.is_empty() {
+            log::debug!("integrity scan: no gaps detected up to {}", current);
+            return Ok(());
+        }
+
+        log::warn!(
+            "integrity scan: detected {} gap(s), backfilling",
+            gaps.len()
+        );
+
+        // Key the fetcher choice off the *oldest* gap: fetch_last90 can only
+        // ever backfill the last 90 days, so if any gap is older than that
+        // it would never be fetched and would end up wrongly marked as a
+        // confirmed non-publishing day below.
+        let oldest_gap = *gaps.first().expect("checked non-empty above");
+        let dates = if current - oldest_gap > Duration::days(90) {
+            currencies::fetch_hist().await?
+        } else {
+            currencies::fetch_last90().await?
+        };
+
+        let mut fetched_days = std::collections::BTreeSet::new();
+        let mut filled = 0;
+        for mut date in dates {
+            let day = date.value_as_date()?;
+            fetched_days.insert(day);
+            if !gaps.contains(&day) {
+                continue;
+            }
+            let key = date_as_key(&date.value)?;
+            //insert EUR base
+            date.currencies.push(Currency {
+                name: "EUR".to_string(),
+                rate: 1.0,
+            });
+            self.put(&key, &date).await?;
+            filled += 1;
+        }
+
+        // Gaps the fetch just proved ECB never published (a holiday our
+        // calendar doesn't know about, or a date outside ECB's coverage)
+        // are recorded so they stop being re-flagged on every future scan.
+        let mut unfillable = 0;
+        for gap in &gaps {
+            if !fetched_days.contains(gap) {
+                self.put(&known_gap_key(*gap), &true).await?;
+                unfillable += 1;
+            }
+        }
+
+        log::info!(
+            "integrity scan: filled {}/{} detected gaps ({} confirmed non-publishing days)",
+            filled,
+            gaps.len(),
+            unfillable
+        );
         Ok(())
     }
 
@@ -236,10 +627,41 @@ mod tests {
         db.put(b"current", &key).await.unwrap();
         db.put(&key, &date).await.unwrap();
         db.inner.flush_async().await.unwrap();
-        let current = db.get_current_rates().await.unwrap();
+        let current = db.get_current_rates(None).await.unwrap();
         assert_eq!(date, current);
     }
 
+    #[test]
+    fn test_rebase() {
+        let date = Date {
+            value: "1999-01-04".to_string(),
+            currencies: vec![
+                Currency { name: "EUR".to_string(), rate: 1.0 },
+                Currency { name: "USD".to_string(), rate: 2.0 },
+                Currency { name: "GBP".to_string(), rate: 0.5 },
+            ],
+        };
+        let rebased = rebase(date, "USD").unwrap();
+        assert_eq!(rebased.currencies[0].rate, 0.5); // EUR
+        assert_eq!(rebased.currencies[1].rate, 1.0); // USD, the new base
+        assert_eq!(rebased.currencies[2].rate, 0.25); // GBP
+    }
+
+    #[test]
+    fn test_rebase_invalid_base() {
+        let date = Date {
+            value: "1999-01-04".to_string(),
+            currencies: vec![Currency { name: "EUR".to_string(), rate: 1.0 }],
+        };
+        let err = rebase(date, "XXX").unwrap_err();
+        // downcastable through the anyhow::Error `?` converted it into, so
+        // handlers::reject can still classify it as a 400, not a 500
+        assert!(matches!(
+            err.downcast_ref::<crate::errors::Error>(),
+            Some(crate::errors::Error::InvalidBase(base)) if base == "XXX"
+        ));
+    }
+
     #[tokio::test]
     async fn test_get_day_rates() {
         let dir = tempdir().unwrap();
@@ -252,10 +674,49 @@ mod tests {
         let key = date_as_key(&date.value).unwrap();
         db.put(&key, &date).await.unwrap();
         db.inner.flush_async().await.unwrap();
-        let current = db.get_day_rates("1999-01-04").await.unwrap().unwrap();
+        let current = db.get_day_rates("1999-01-04", None).await.unwrap().unwrap();
         assert_eq!(date, current);
     }
 
+    #[tokio::test]
+    async fn test_get_day_rates_or_latest_falls_back_to_preceding_friday() {
+        let dir = tempdir().unwrap();
+        let path = dir.into_path();
+        let db = Db::open(path.join("db")).unwrap();
+        // 2020-01-03 was a Friday; ECB does not publish on the following weekend.
+        let date = Date {
+            value: "2020-01-03".to_string(),
+            currencies: Vec::new(),
+        };
+        let key = date_as_key(&date.value).unwrap();
+        db.put(&key, &date).await.unwrap();
+        db.inner.flush_async().await.unwrap();
+
+        let resolved = db
+            .get_day_rates_or_latest("2020-01-05", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.value, "2020-01-03");
+    }
+
+    #[tokio::test]
+    async fn test_get_day_rates_or_latest_none_before_earliest_entry() {
+        let dir = tempdir().unwrap();
+        let path = dir.into_path();
+        let db = Db::open(path.join("db")).unwrap();
+        let date = Date {
+            value: "1999-01-04".to_string(),
+            currencies: Vec::new(),
+        };
+        let key = date_as_key(&date.value).unwrap();
+        db.put(&key, &date).await.unwrap();
+        db.inner.flush_async().await.unwrap();
+
+        let resolved = db.get_day_rates_or_latest("1998-01-01", None).await.unwrap();
+        assert!(resolved.is_none());
+    }
+
     #[tokio::test]
     async fn test_get_range_rates() {
         let dir = tempdir().unwrap();
@@ -286,7 +747,166 @@ mod tests {
 
         let begining = NaiveDate::from_str("1999-01-04").unwrap();
         let end = NaiveDate::from_str("2012-01-04").unwrap();
-        let dates = db.get_range_rates(begining, end).await.unwrap();
+        let dates = db.get_range_rates(begining, end, None).await.unwrap();
         assert_eq!(dates.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_get_range_stats() {
+        let dir = tempdir().unwrap();
+        let path = dir.into_path();
+        let db = Db::open(path.join("db")).unwrap();
+
+        for (day, rate) in &[("1999-01-04", 1.0), ("1999-01-05", 2.0), ("1999-01-06", 3.0)] {
+            let date = Date {
+                value: day.to_string(),
+                currencies: vec![Currency { name: "USD".to_string(), rate: *rate }],
+            };
+            let key = date_as_key(&date.value).unwrap();
+            db.put(&key, &date).await.unwrap();
+        }
+        db.inner.flush_async().await.unwrap();
+
+        let begining = NaiveDate::from_str("1999-01-04").unwrap();
+        let end = NaiveDate::from_str("1999-01-06").unwrap();
+        let stats = db.get_range_stats(begining, end, "USD").await.unwrap();
+
+        assert_eq!(stats.start_date, "1999-01-04");
+        assert_eq!(stats.end_date, "1999-01-06");
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 3.0);
+        assert_eq!(stats.mean, 2.0);
+        assert_eq!(stats.first, 1.0);
+        assert_eq!(stats.last, 3.0);
+        assert_eq!(stats.change_pct, 200.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_range_stats_unknown_symbol() {
+        let dir = tempdir().unwrap();
+        let path = dir.into_path();
+        let db = Db::open(path.join("db")).unwrap();
+        let date = Date {
+            value: "1999-01-04".to_string(),
+            currencies: vec![Currency { name: "USD".to_string(), rate: 1.0 }],
+        };
+        let key = date_as_key(&date.value).unwrap();
+        db.put(&key, &date).await.unwrap();
+        db.inner.flush_async().await.unwrap();
+
+        let day = NaiveDate::from_str("1999-01-04").unwrap();
+        let err = db.get_range_stats(day, day, "GBP").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::errors::Error>(),
+            Some(crate::errors::Error::InvalidSymbol)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_range_stats_invalid_date_range_downcasts_to_api_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.into_path();
+        let db = Db::open(path.join("db")).unwrap();
+
+        let start = NaiveDate::from_str("2000-01-01").unwrap();
+        let end = NaiveDate::from_str("1999-01-01").unwrap();
+        let err = db.get_range_stats(start, end, "USD").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::errors::Error>(),
+            Some(crate::errors::Error::InvalidDateRange)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_crypto_current() {
+        let dir = tempdir().unwrap();
+        let path = dir.into_path();
+        let db = Db::open(path.join("db")).unwrap();
+
+        let quote = Quote { timestamp: 915408000, price: 5000.0 };
+        db.put(&crypto_key("bitcoin", "usd", quote.timestamp), &quote)
+            .await
+            .unwrap();
+        db.put(&crypto_current_key("bitcoin", "usd"), &quote.timestamp)
+            .await
+            .unwrap();
+        db.inner.flush_async().await.unwrap();
+
+        let current = db.get_crypto_current("bitcoin", "usd").await.unwrap();
+        assert_eq!(current, quote);
+    }
+
+    #[test]
+    fn test_crypto_key_does_not_collide_with_fiat_key() {
+        let fiat_key = date_as_key("1999-01-04").unwrap();
+        let crypto_key = crypto_key("bitcoin", "usd", 915408000);
+        assert_ne!(fiat_key, crypto_key);
+    }
+
+    #[test]
+    fn test_business_days_skips_weekends() {
+        // 2020-01-03 is a Friday, 2020-01-06 the following Monday.
+        let start = NaiveDate::from_str("2020-01-03").unwrap();
+        let end = NaiveDate::from_str("2020-01-06").unwrap();
+        let days: Vec<NaiveDate> = business_days(start, end).collect();
+        assert_eq!(
+            days,
+            vec![
+                NaiveDate::from_str("2020-01-03").unwrap(),
+                NaiveDate::from_str("2020-01-06").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_business_days_skips_ecb_holidays() {
+        // 2020-05-01 (Labour Day) is a weekday but an ECB closing day, so it
+        // must not show up as an expected day; 2020-12-25/26 are closing days too.
+        let days: Vec<NaiveDate> = business_days(
+            NaiveDate::from_str("2020-04-30").unwrap(),
+            NaiveDate::from_str("2020-05-04").unwrap(),
+        )
+        .collect();
+        assert_eq!(
+            days,
+            vec![
+                NaiveDate::from_str("2020-04-30").unwrap(),
+                NaiveDate::from_str("2020-05-04").unwrap(),
+            ]
+        );
+
+        assert!(is_ecb_holiday(NaiveDate::from_str("2020-12-25").unwrap()));
+        assert!(is_ecb_holiday(NaiveDate::from_str("2020-12-26").unwrap()));
+        assert!(is_ecb_holiday(NaiveDate::from_str("2020-01-01").unwrap()));
+    }
+
+    #[test]
+    fn test_is_ecb_holiday_good_friday_and_easter_monday() {
+        // Easter Sunday 2020 was 2020-04-12.
+        assert!(is_ecb_holiday(NaiveDate::from_str("2020-04-10").unwrap())); // Good Friday
+        assert!(is_ecb_holiday(NaiveDate::from_str("2020-04-13").unwrap())); // Easter Monday
+        assert!(!is_ecb_holiday(NaiveDate::from_str("2020-04-12").unwrap())); // Easter Sunday itself is a weekend anyway
+    }
+
+    #[tokio::test]
+    async fn test_find_gaps_detects_missing_weekday() {
+        let dir = tempdir().unwrap();
+        let path = dir.into_path();
+        let db = Db::open(path.join("db")).unwrap();
+
+        // 2020-01-03 (Fri) and 2020-01-07 (Tue) are stored, 2020-01-06 (Mon) is missing.
+        for day in &["2020-01-03", "2020-01-07"] {
+            let date = Date {
+                value: day.to_string(),
+                currencies: Vec::new(),
+            };
+            let key = date_as_key(&date.value).unwrap();
+            db.put(&key, &date).await.unwrap();
+        }
+        db.inner.flush_async().await.unwrap();
+
+        let current = NaiveDate::from_str("2020-01-07").unwrap();
+        let gaps = db.find_gaps(current).await.unwrap();
+        assert_eq!(gaps, vec![NaiveDate::from_str("2020-01-06").unwrap()]);
+    }
 }
@@ -18,7 +18,8 @@ pub async fn recover(err: Rejection) -> Result<Box<dyn Reply>, Rejection> {
             | Error::InvalidSymbol
             | Error::MissingDateBoundaries
             | Error::InvalidDateRange
-            | Error::InvalidBase(_) => {
+            | Error::InvalidBase(_)
+            | Error::InvalidFilter(_) => {
                 log::trace!("api reject, {}", err);
                 ErrorMessage {
                     code: StatusCode::BAD_REQUEST.as_u16(),
@@ -68,6 +69,8 @@ pub enum Error {
     InvalidDateFormat(&'static str, String),
     #[error("`{0}` is an invalid base currency")]
     InvalidBase(String),
+    #[error("`{0}` is not a valid filter expression")]
+    InvalidFilter(String),
     #[error("empty currency dataset, should have at least 1 element")]
     EmpyDataset,
     #[error("symbol list contains invalid symbols")]
@@ -0,0 +1,189 @@
+// a small filter-expression language for narrowing down the `currencies`
+// vector in API responses, parsed with nom; e.g. "rate > 1.1 AND name != USD"
+// parentheses group, AND binds tighter than OR
+
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{alphanumeric1, char, multispace0};
+use nom::combinator::map;
+use nom::number::complete::double;
+use nom::sequence::{delimited, preceded, tuple};
+use nom::IResult;
+
+use crate::currencies::Currency;
+use crate::errors::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Name,
+    Rate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Code(String),
+    Number(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Cmp { field: Field, op: Op, value: Value },
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Result<Filter, Error> {
+        match expr(input) {
+            Ok((remaining, filter)) if remaining.trim().is_empty() => Ok(filter),
+            _ => Err(Error::InvalidFilter(input.to_string())),
+        }
+    }
+
+    pub fn evaluate(&self, currency: &Currency) -> bool {
+        match self {
+            Filter::And(lhs, rhs) => lhs.evaluate(currency) && rhs.evaluate(currency),
+            Filter::Or(lhs, rhs) => lhs.evaluate(currency) || rhs.evaluate(currency),
+            Filter::Cmp { field, op, value } => compare(field, op, value, currency),
+        }
+    }
+}
+
+fn compare(field: &Field, op: &Op, value: &Value, currency: &Currency) -> bool {
+    match (field, value) {
+        (Field::Name, Value::Code(code)) => match op {
+            Op::Eq => currency.name == *code,
+            Op::Ne => currency.name != *code,
+            // ordering comparisons on a currency code are nonsensical, so they never match.
+            _ => false,
+        },
+        (Field::Rate, Value::Number(n)) => match op {
+            Op::Eq => (currency.rate - n).abs() < f64::EPSILON,
+            Op::Ne => (currency.rate - n).abs() >= f64::EPSILON,
+            Op::Gt => currency.rate > *n,
+            Op::Gte => currency.rate >= *n,
+            Op::Lt => currency.rate < *n,
+            Op::Lte => currency.rate <= *n,
+        },
+        // field/value type mismatch, e.g. `rate = GBP`.
+        _ => false,
+    }
+}
+
+fn ws<'a, F, O>(inner: F) -> impl Fn(&'a str) -> IResult<&'a str, O>
+where
+    F: Fn(&'a str) -> IResult<&'a str, O>,
+{
+    move |input| delimited(multispace0, &inner, multispace0)(input)
+}
+
+fn field(input: &str) -> IResult<&str, Field> {
+    alt((
+        map(tag("name"), |_| Field::Name),
+        map(tag("rate"), |_| Field::Rate),
+    ))(input)
+}
+
+fn op(input: &str) -> IResult<&str, Op> {
+    alt((
+        map(tag(">="), |_| Op::Gte),
+        map(tag("<="), |_| Op::Lte),
+        map(tag("!="), |_| Op::Ne),
+        map(tag("="), |_| Op::Eq),
+        map(tag(">"), |_| Op::Gt),
+        map(tag("<"), |_| Op::Lt),
+    ))(input)
+}
+
+fn value(input: &str) -> IResult<&str, Value> {
+    alt((
+        map(double, Value::Number),
+        map(alphanumeric1, |code: &str| Value::Code(code.to_string())),
+    ))(input)
+}
+
+fn term(input: &str) -> IResult<&str, Filter> {
+    map(
+        tuple((ws(field), ws(op), ws(value))),
+        |(field, op, value)| Filter::Cmp { field, op, value },
+    )(input)
+}
+
+fn primary(input: &str) -> IResult<&str, Filter> {
+    alt((delimited(ws(char('(')), expr, ws(char(')'))), term))(input)
+}
+
+fn and_expr(input: &str) -> IResult<&str, Filter> {
+    let (mut input, mut result) = primary(input)?;
+    while let Ok((rest, rhs)) = preceded(ws(tag("AND")), primary)(input) {
+        result = Filter::And(Box::new(result), Box::new(rhs));
+        input = rest;
+    }
+    Ok((input, result))
+}
+
+fn expr(input: &str) -> IResult<&str, Filter> {
+    let (mut input, mut result) = and_expr(input)?;
+    while let Ok((rest, rhs)) = preceded(ws(tag("OR")), and_expr)(input) {
+        result = Filter::Or(Box::new(result), Box::new(rhs));
+        input = rest;
+    }
+    Ok((input, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn currency(name: &str, rate: f64) -> Currency {
+        Currency {
+            name: name.to_string(),
+            rate,
+        }
+    }
+
+    #[test]
+    fn test_parse_simple_cmp() {
+        let filter = Filter::parse("rate > 1.1").unwrap();
+        assert_eq!(
+            filter,
+            Filter::Cmp {
+                field: Field::Rate,
+                op: Op::Gt,
+                value: Value::Number(1.1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        let filter = Filter::parse("name = GBP OR rate > 1.1 AND name != USD").unwrap();
+        // left to right, AND tighter than OR:
+        // name = GBP OR (rate > 1.1 AND name != USD)
+        assert!(filter.evaluate(&currency("GBP", 0.9)));
+        assert!(filter.evaluate(&currency("JPY", 1.2)));
+        assert!(!filter.evaluate(&currency("USD", 1.2)));
+    }
+
+    #[test]
+    fn test_parentheses_override_precedence() {
+        let filter = Filter::parse("(name = GBP OR rate > 1.1) AND name != USD").unwrap();
+        assert!(!filter.evaluate(&currency("USD", 1.2)));
+        assert!(filter.evaluate(&currency("JPY", 1.2)));
+    }
+
+    #[test]
+    fn test_invalid_filter_is_rejected() {
+        assert!(Filter::parse("rate ??? 1.1").is_err());
+    }
+}
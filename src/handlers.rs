@@ -1,10 +1,14 @@
-use std::sync::Arc;
 use std::cmp::Ordering;
+use std::str::FromStr;
+use std::sync::Arc;
 
 use askama::Template;
+use chrono::naive::NaiveDate;
 
 use crate::currencies::Currency;
 use crate::db::Db;
+use crate::errors::Error as ApiError;
+use crate::filter::Filter;
 
 #[derive(Template)]
 #[template(path = "index.html")]
@@ -13,11 +17,26 @@ struct CurrenciesTemplate<'a> {
     currencies: &'a [Currency],
 }
 
-pub async fn index(db: Arc<Db>) -> Result<impl warp::Reply, warp::Rejection> {
+// downcasts back to crate::errors::Error when one caused it, so errors::recover
+// can classify it as a 400 instead of falling through to a 500
+fn reject(err: anyhow::Error) -> warp::Rejection {
+    match err.downcast::<crate::errors::Error>() {
+        Ok(api_err) => warp::reject::custom(api_err),
+        Err(err) => warp::reject::custom(crate::errors::Error::DatabaseError(err.to_string())),
+    }
+}
+
+pub async fn index(
+    db: Arc<Db>,
+    base: Option<String>,
+    filter: Option<String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
     let mut date = db
-        .get_current_rates()
+        .get_current_rates(base.as_deref())
         .await
-        .map_err(|e| warp::reject::custom(e))?;
+        .map_err(reject)?;
+
+    apply_filter(&mut date, &filter)?;
 
     // order currencies so that EUR comes first then gomes USD and then GBP
     date.currencies.sort_by(|curr1, curr2| {
@@ -40,4 +59,121 @@ pub async fn index(db: Arc<Db>) -> Result<impl warp::Reply, warp::Rejection> {
     .map_err(|e| warp::reject::custom(e))?;
 
     Ok(warp::reply::html(rendered))
+}
+
+fn parse_date(field: &'static str, value: &str) -> Result<NaiveDate, warp::Rejection> {
+    NaiveDate::from_str(value)
+        .map_err(|_| warp::reject::custom(ApiError::InvalidDateFormat(field, value.to_string())))
+}
+
+fn apply_filter(date: &mut crate::currencies::Date, filter: &Option<String>) -> Result<(), warp::Rejection> {
+    if let Some(expression) = filter {
+        let filter = Filter::parse(expression).map_err(|e| warp::reject::custom(e))?;
+        date.currencies.retain(|currency| filter.evaluate(currency));
+    }
+    Ok(())
+}
+
+pub async fn day_rates(
+    db: Arc<Db>,
+    day: String,
+    base: Option<String>,
+    filter: Option<String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut date = db
+        .get_day_rates_or_latest(&day, base.as_deref())
+        .await
+        .map_err(reject)?
+        .ok_or_else(|| warp::reject::custom(ApiError::DateNotFound(day.clone())))?;
+
+    apply_filter(&mut date, &filter)?;
+
+    Ok(warp::reply::json(&date))
+}
+
+pub async fn range_rates(
+    db: Arc<Db>,
+    start_at: String,
+    end_at: String,
+    base: Option<String>,
+    filter: Option<String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let start_at = parse_date("start_at", &start_at)?;
+    let end_at = parse_date("end_at", &end_at)?;
+
+    let mut dates = db
+        .get_range_rates(start_at, end_at, base.as_deref())
+        .await
+        .map_err(reject)?;
+
+    for date in dates.iter_mut() {
+        apply_filter(date, &filter)?;
+    }
+
+    Ok(warp::reply::json(&dates))
+}
+
+pub async fn range_stats(
+    db: Arc<Db>,
+    start_at: String,
+    end_at: String,
+    symbol: String,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let start_at = parse_date("start_at", &start_at)?;
+    let end_at = parse_date("end_at", &end_at)?;
+
+    let stats = db
+        .get_range_stats(start_at, end_at, &symbol)
+        .await
+        .map_err(reject)?;
+
+    Ok(warp::reply::json(&stats))
+}
+
+pub async fn crypto_current(
+    db: Arc<Db>,
+    coin: String,
+    vs_currency: String,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let quote = db
+        .get_crypto_current(&coin, &vs_currency)
+        .await
+        .map_err(reject)?;
+
+    Ok(warp::reply::json(&quote))
+}
+
+pub async fn crypto_day(
+    db: Arc<Db>,
+    coin: String,
+    vs_currency: String,
+    day: String,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let day = parse_date("day", &day)?;
+
+    let quote = db
+        .get_crypto_day(&coin, &vs_currency, day)
+        .await
+        .map_err(reject)?
+        .ok_or_else(|| warp::reject::custom(ApiError::DateNotFound(day.to_string())))?;
+
+    Ok(warp::reply::json(&quote))
+}
+
+pub async fn crypto_range(
+    db: Arc<Db>,
+    coin: String,
+    vs_currency: String,
+    start_at: String,
+    end_at: String,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let start_at = parse_date("start_at", &start_at)?;
+    let end_at = parse_date("end_at", &end_at)?;
+
+    let quotes = db
+        .get_crypto_range(&coin, &vs_currency, start_at, end_at)
+        .await
+        .map_err(reject)?;
+
+    Ok(warp::reply::json(&quotes))
 }
\ No newline at end of file